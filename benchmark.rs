@@ -1,39 +1,44 @@
-use std::time::{Instant, Duration};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
-const ITERATIONS: u64 = 1_000_000_000;
+// Número de unidades de trabajo ejecutadas dentro de una sola muestra medida.
+// Lo bastante grande para eclipsar el coste de leer el reloj, lo bastante
+// pequeño para que una muestra sea rápida.
+const INNER_ITERS: u64 = 1_000_000;
+// Cuántas muestras recoge `measure` por benchmark.
+const SAMPLES: usize = 100;
+// Remuestreos bootstrap usados para derivar el intervalo de confianza del 95%.
+const RESAMPLES: usize = 100_000;
+// El calentamiento ejecuta el cierre de forma encadenada hasta que transcurre
+// este tiempo de reloj, para que la CPU alcance una frecuencia turbo estable
+// antes de empezar a muestrear.
+const WARMUP: Duration = Duration::from_secs(1);
 
 // --- 1. Despacho Dinámico (como Funciones Virtuales) ---
 
+// Cada unidad de trabajo toma un valor, le suma una constante y lo devuelve a
+// través de `black_box`, de modo que el optimizador no puede ni ver el
+// resultado como constante ni elidir la llamada despachada.
 trait DoWork {
-    fn do_work(&self);
+    fn do_work(&self, x: u64) -> u64;
 }
 
 struct ConcreteWorker;
 
 impl DoWork for ConcreteWorker {
     #[inline(never)] // Prevenir que el compilador sea demasiado listo
-    fn do_work(&self) {
-        unsafe { std::arch::asm!(""); }
-    }
-}
-
-fn run_dynamic_dispatch(worker: &dyn DoWork) {
-    for _ in 0..ITERATIONS {
-        worker.do_work();
+    fn do_work(&self, x: u64) -> u64 {
+        black_box(x.wrapping_add(1))
     }
 }
 
 // --- 2. Puntero a Función ---
 
 #[inline(never)]
-fn work_function() {
-    unsafe { std::arch::asm!(""); }
-}
-
-fn run_function_pointer(func: fn()) {
-    for _ in 0..ITERATIONS {
-        func();
-    }
+fn work_function(x: u64) -> u64 {
+    black_box(x.wrapping_add(1))
 }
 
 // --- 3. Despacho Estático (Wrapper genérico) ---
@@ -42,58 +47,601 @@ struct InnerObject;
 
 impl InnerObject {
     #[inline(never)]
-    fn action(&self) {
-        unsafe { std::arch::asm!(""); }
+    fn action(&self, x: u64) -> u64 {
+        black_box(x.wrapping_add(1))
+    }
+}
+
+// --- 4. Un segundo worker concreto, para que los casos heterogéneo y de enum
+// recorran más de una vtable / variante ---
+
+struct OtherWorker;
+
+impl DoWork for OtherWorker {
+    #[inline(never)]
+    fn do_work(&self, x: u64) -> u64 {
+        black_box(x.wrapping_add(3))
+    }
+}
+
+// Despacho por enum: un match concreto sobre las variantes de worker, sin
+// vtable involucrada.
+enum WorkerEnum {
+    Concrete(ConcreteWorker),
+    Other(OtherWorker),
+}
+
+impl WorkerEnum {
+    #[inline(never)]
+    fn do_work(&self, x: u64) -> u64 {
+        match self {
+            WorkerEnum::Concrete(w) => w.do_work(x),
+            WorkerEnum::Other(w) => w.do_work(x),
+        }
+    }
+}
+
+// --- Fuentes de reloj ---
+
+// Tomando prestada la distinción rápido-vs-preciso de dipstick: el harness es
+// genérico sobre un reloj para que el llamador pueda cambiar resolución por
+// coste de lectura. `now` devuelve un handle opaco y `elapsed_ns` convierte el
+// hueco entre dos handles en nanosegundos.
+trait Clock {
+    type Handle;
+    fn now(&self) -> Self::Handle;
+    fn elapsed_ns(&self, since: &Self::Handle) -> f64;
+}
+
+// Reloj monótono de alta precisión respaldado por `Instant`.
+struct InstantClock;
+
+impl Clock for InstantClock {
+    type Handle = Instant;
+
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn elapsed_ns(&self, since: &Instant) -> f64 {
+        since.elapsed().as_nanos() as f64
+    }
+}
+
+// Una segunda fuente de reloj respaldada por el reloj de pared en lugar del
+// temporizador monótono, cambiando resolución y monotonía por un camino de
+// lectura distinto. No se asume que su lectura sea más barata: en Linux tanto
+// `Instant` como `SystemTime` pasan por el vDSO, así que la calibración la
+// cronometra junto a `InstantClock` e imprime ambos costes, dejando ver al
+// usuario cuánto cuesta la propia fuente de reloj antes de fiarse de una
+// muestra corta.
+struct CoarseClock;
+
+impl Clock for CoarseClock {
+    type Handle = SystemTime;
+
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn elapsed_ns(&self, since: &SystemTime) -> f64 {
+        since.elapsed().unwrap_or_default().as_nanos() as f64
+    }
+}
+
+// --- Harness de muestreo ---
+
+// Un pequeño LCG determinista para que el bootstrap sea reproducible sin
+// depender del crate `rand`. Las constantes son las del MMIX de Knuth.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0
+    }
+
+    // Índice uniforme en `0..n`. Aquí `n` es siempre un número pequeño de
+    // muestras, así que el sesgo del módulo es despreciable.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() >> 33) as usize % n
+    }
+}
+
+// Percentil interpolado linealmente sobre un slice ya ordenado.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+    if lo == hi {
+        sorted[lo]
+    } else {
+        let frac = rank - lo as f64;
+        sorted[lo] * (1.0 - frac) + sorted[hi] * frac
+    }
+}
+
+// Clasificación de atípicos por vallas de Tukey. Devuelve los recuentos
+// (leves, severos): las muestras fuera de las vallas 1.5·IQR son leves, las
+// que quedan fuera de las vallas 3·IQR son severas.
+fn classify_outliers(sorted: &[f64]) -> (usize, usize) {
+    let q1 = percentile(sorted, 25.0);
+    let q3 = percentile(sorted, 75.0);
+    let iqr = q3 - q1;
+    let mild_lo = q1 - 1.5 * iqr;
+    let mild_hi = q3 + 1.5 * iqr;
+    let severe_lo = q1 - 3.0 * iqr;
+    let severe_hi = q3 + 3.0 * iqr;
+    let mut mild = 0;
+    let mut severe = 0;
+    for &v in sorted {
+        if v < severe_lo || v > severe_hi {
+            severe += 1;
+        } else if v < mild_lo || v > mild_hi {
+            mild += 1;
+        }
+    }
+    (mild, severe)
+}
+
+// Intervalo de confianza del 95% de la media por remuestreo bootstrap: se
+// extraen RESAMPLES remuestreos con reemplazo, se toma la media de cada uno y
+// se leen los percentiles 2.5 y 97.5 de esas medias.
+fn bootstrap_ci(samples: &[f64], rng: &mut Lcg) -> (f64, f64) {
+    let n = samples.len();
+    let mut means = Vec::with_capacity(RESAMPLES);
+    for _ in 0..RESAMPLES {
+        let mut sum = 0.0;
+        for _ in 0..n {
+            sum += samples[rng.below(n)];
+        }
+        means.push(sum / n as f64);
+    }
+    means.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    (percentile(&means, 2.5), percentile(&means, 97.5))
+}
+
+// Recoge SAMPLES tiempos por iteración de `f` a través de `clock`.
+fn collect_samples<C: Clock>(clock: &C, f: &mut impl FnMut()) -> Vec<f64> {
+    let mut per_iter = Vec::with_capacity(SAMPLES);
+    for _ in 0..SAMPLES {
+        let start = clock.now();
+        for _ in 0..INNER_ITERS {
+            f();
+        }
+        per_iter.push(clock.elapsed_ns(&start) / INNER_ITERS as f64);
     }
+    per_iter
+}
+
+// Cronometra un cuerpo de bucle vacío para estimar el coste por iteración del
+// bucle y del propio `clock`, de modo que pueda restarse de las mediciones
+// reales. Usa la mediana de las muestras para mayor robustez.
+fn calibrate<C: Clock>(clock: &C) -> f64 {
+    let mut empty = || {
+        black_box(0u64);
+    };
+    let mut per_iter = collect_samples(clock, &mut empty);
+    per_iter.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    percentile(&per_iter, 50.0)
 }
 
-// Wrapper genérico que usa despacho estático
-struct GenericWrapper<T> {
-    inner: T,
+// Estimación puntual e intervalo de confianza del 95% de un benchmark, en
+// ns/iter netos del coste del reloj. Esto es lo que se persiste como baseline.
+struct BenchResult {
+    point: f64,
+    ci_lo: f64,
+    ci_hi: f64,
+}
+
+// Mide el coste por iteración de `f` a través de `clock`, imprime una
+// estimación puntual neta de `overhead` con su intervalo de confianza del 95%
+// y los recuentos de atípicos, y devuelve el resultado neto para comparar con
+// el baseline.
+fn measure<C: Clock>(clock: &C, overhead: f64, name: &str, mut f: impl FnMut()) -> BenchResult {
+    // Calentamiento: martillear el cierre hasta que la CPU se estabilice.
+    let warm_start = Instant::now();
+    while warm_start.elapsed() < WARMUP {
+        for _ in 0..INNER_ITERS {
+            f();
+        }
+    }
+
+    let per_iter = collect_samples(clock, &mut f);
+
+    let gross = per_iter.iter().sum::<f64>() / per_iter.len() as f64;
+    let net = (gross - overhead).max(0.0);
+
+    let mut sorted = per_iter.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let (mild, severe) = classify_outliers(&sorted);
+
+    let mut rng = Lcg(0x2545F4914F6CDD1D);
+    let (lo, hi) = bootstrap_ci(&per_iter, &mut rng);
+    let ci_lo = (lo - overhead).max(0.0);
+    let ci_hi = (hi - overhead).max(0.0);
+
+    println!("{name}:");
+    println!(
+        "   {net:.3} ns/iter neto  [IC 95% {ci_lo:.3} .. {ci_hi:.3}]  (bruto {gross:.3}, reloj {overhead:.3})"
+    );
+    println!("   atípicos: {mild} leves, {severe} severos (de {SAMPLES} muestras)");
+
+    BenchResult {
+        point: net,
+        ci_lo,
+        ci_hi,
+    }
 }
 
-impl<T: Fn()> FnOnce<()> for GenericWrapper<T> {
-    type Output = ();
-    extern "rust-call" fn call_once(self, _args: ()) -> Self::Output {
-        (self.inner)();
+// --- Persistencia de baseline ---
+
+// El fichero en disco para un baseline con nombre.
+fn baseline_path(name: &str) -> String {
+    format!("{name}.baseline.json")
+}
+
+// Serializa los resultados de la ejecución a un objeto JSON indexado por el
+// nombre del benchmark. El formato es artesanal para mantener el crate sin
+// dependencias.
+fn save_baseline(name: &str, results: &[(String, BenchResult)]) -> std::io::Result<()> {
+    let mut out = String::from("{\n");
+    for (i, (bench, r)) in results.iter().enumerate() {
+        out.push_str(&format!(
+            "  {}: {{\"point\": {}, \"ci_lo\": {}, \"ci_hi\": {}}}",
+            json_string(bench),
+            r.point,
+            r.ci_lo,
+            r.ci_hi
+        ));
+        if i + 1 < results.len() {
+            out.push(',');
+        }
+        out.push('\n');
+    }
+    out.push_str("}\n");
+    std::fs::write(baseline_path(name), out)
+}
+
+// Carga un baseline guardado previamente, o `None` si aún no existe.
+fn load_baseline(name: &str) -> Option<HashMap<String, BenchResult>> {
+    let text = std::fs::read_to_string(baseline_path(name)).ok()?;
+    Some(parse_baseline(&text))
+}
+
+// Escapa una cadena como literal JSON. Los nombres de benchmark solo contienen
+// comillas o barras invertidas en casos patológicos, pero se manejan igual.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// Un parser mínimo para el subconjunto de JSON que emite `save_baseline`: un
+// objeto de nombres de benchmark a objetos `{point, ci_lo, ci_hi}`. Opera
+// sobre bytes y reensambla el contenido de las cadenas con
+// `String::from_utf8_lossy` para que los nombres no ASCII sobrevivan al
+// ciclo de ida y vuelta.
+struct BaselineParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BaselineParser<'a> {
+    fn skip_ws(&mut self) {
+        while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    // Consume `c` si es el siguiente byte que no es espacio en blanco.
+    fn eat(&mut self, c: u8) -> bool {
+        self.skip_ws();
+        if self.pos < self.bytes.len() && self.bytes[self.pos] == c {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.skip_ws();
+        if self.pos >= self.bytes.len() || self.bytes[self.pos] != b'"' {
+            return None;
+        }
+        self.pos += 1;
+        let mut buf = Vec::new();
+        while self.pos < self.bytes.len() && self.bytes[self.pos] != b'"' {
+            if self.bytes[self.pos] == b'\\' {
+                self.pos += 1;
+                if self.pos >= self.bytes.len() {
+                    break;
+                }
+            }
+            buf.push(self.bytes[self.pos]);
+            self.pos += 1;
+        }
+        self.pos += 1; // comilla de cierre
+        Some(String::from_utf8_lossy(&buf).into_owned())
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        self.skip_ws();
+        let start = self.pos;
+        while self.pos < self.bytes.len()
+            && matches!(
+                self.bytes[self.pos],
+                b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E'
+            )
+        {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.pos])
+            .ok()?
+            .parse()
+            .ok()
     }
 }
 
-impl<T: FnMut()> FnMut<()> for GenericWrapper<T> {
-    extern "rust-call" fn call_mut(&mut self, _args: ()) -> Self::Output {
-        (self.inner)();
+fn parse_baseline(text: &str) -> HashMap<String, BenchResult> {
+    let mut map = HashMap::new();
+    let mut p = BaselineParser {
+        bytes: text.as_bytes(),
+        pos: 0,
+    };
+    if !p.eat(b'{') {
+        return map;
+    }
+    loop {
+        if p.eat(b'}') {
+            break;
+        }
+        let name = match p.string() {
+            Some(n) => n,
+            None => break,
+        };
+        p.eat(b':');
+        p.eat(b'{');
+        let (mut point, mut ci_lo, mut ci_hi) = (0.0, 0.0, 0.0);
+        loop {
+            if p.eat(b'}') {
+                break;
+            }
+            let field = match p.string() {
+                Some(f) => f,
+                None => break,
+            };
+            p.eat(b':');
+            let value = p.number().unwrap_or(0.0);
+            match field.as_str() {
+                "point" => point = value,
+                "ci_lo" => ci_lo = value,
+                "ci_hi" => ci_hi = value,
+                _ => {}
+            }
+            p.eat(b',');
+        }
+        map.insert(name, BenchResult { point, ci_lo, ci_hi });
+        p.eat(b',');
     }
+    map
 }
 
-fn run_static_dispatch<F: Fn()>(f: F) {
-    for _ in 0..ITERATIONS {
-        f();
+// Compara un resultado nuevo contra uno guardado. El veredicto lo decide si los
+// intervalos de confianza se solapan; menos ns/iter es mejor.
+fn verdict(new: &BenchResult, old: &BenchResult) -> &'static str {
+    if new.ci_hi < old.ci_lo {
+        "mejorado"
+    } else if new.ci_lo > old.ci_hi {
+        "regresión"
+    } else {
+        "dentro del ruido"
     }
 }
 
+// Una unidad de trabajo con nombre registrada contra el harness `measure`.
+type Benchmark<'a> = (&'a str, Box<dyn FnMut() + 'a>);
 
 fn main() {
-    // --- Test 1: Despacho Dinámico ---
-    println!("1. Benchmark de Despacho Dinámico (dyn Trait)...");
-    let worker = ConcreteWorker;
-    let start = Instant::now();
-    run_dynamic_dispatch(&worker);
-    let duration = start.elapsed();
-    println!("   Tiempo total: {:.6?} segundos", duration.as_secs_f64());
-
-    // --- Test 2: Puntero a Función ---
-    println!("\n2. Benchmark de Puntero a Función...");
-    let start = Instant::now();
-    run_function_pointer(work_function);
-    let duration = start.elapsed();
-    println!("   Tiempo total: {:.6?} segundos", duration.as_secs_f64());
-
-    // --- Test 3: Despacho Estático (Genéricos) ---
-    println!("\n3. Benchmark de Despacho Estático (Genéricos)...");
+    // CLI: `--baseline <nombre>` carga un baseline guardado para comparar,
+    // `--save-baseline <nombre>` escribe los resultados de esta ejecución como
+    // baseline.
+    let args: Vec<String> = std::env::args().collect();
+    let mut load_name = None;
+    let mut save_name = None;
+    let mut it = args.iter().skip(1);
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--baseline" => load_name = it.next().cloned(),
+            "--save-baseline" => save_name = it.next().cloned(),
+            _ => {}
+        }
+    }
+
+    let baseline = match load_name.as_deref() {
+        Some(name) => match load_baseline(name) {
+            Some(b) => Some(b),
+            None => {
+                println!("No se encontró el baseline '{name}'; se omite la comparación.\n");
+                None
+            }
+        },
+        None => None,
+    };
+
+    // Los números reportados usan el reloj preciso; la calibración resta su
+    // coste de lectura. El coste del reloj grueso se imprime para comparar.
+    let clock = InstantClock;
+    let overhead = calibrate(&clock);
+    let coarse_overhead = calibrate(&CoarseClock);
+    println!(
+        "Coste del reloj: preciso {overhead:.3} ns/iter, grueso {coarse_overhead:.3} ns/iter\n"
+    );
+
+    // Dueños de los objetos despachados. Deben sobrevivir al registro, así que
+    // se declaran por adelantado.
     let inner = InnerObject;
-    let wrapper = || inner.action(); // Usamos un cierre, es lo más idiomático
-    let start = Instant::now();
-    run_static_dispatch(wrapper);
-    let duration = start.elapsed();
-    println!("   Tiempo total: {:.6?} segundos", duration.as_secs_f64());
-}
\ No newline at end of file
+    let worker = ConcreteWorker;
+    let enum_a = WorkerEnum::Concrete(ConcreteWorker);
+    let enum_b = WorkerEnum::Other(OtherWorker);
+    let boxed: Box<dyn DoWork> = Box::new(ConcreteWorker);
+    let arc: Arc<dyn DoWork> = Arc::new(ConcreteWorker);
+    let a = ConcreteWorker;
+    let b = OtherWorker;
+    let mixed: [&dyn DoWork; 4] = [&a, &b, &b, &a];
+
+    // Una secuencia de selección pseudoaleatoria, precalculada una vez y
+    // compartida por todos los benchmarks, para que el coste ajeno al despacho
+    // por iteración (una lectura de array enmascarada) sea idéntico en toda la
+    // suite y solo varíe el mecanismo de despacho. Cada cierre indexa un array
+    // de handles de longitud `mixed.len()` del mismo modo; los casos de un solo
+    // destino rellenan ese array con handles idénticos para que el destino sea
+    // predecible, mientras que el caso heterogéneo lo rellena con tipos
+    // concretos distintos para que el destino de la vtable sea genuinamente
+    // impredecible.
+    const SEQ_LEN: usize = 256;
+    let mut seq_rng = Lcg(0x9E3779B97F4A7C15);
+    let seq: [usize; SEQ_LEN] = std::array::from_fn(|_| seq_rng.below(mixed.len()));
+
+    // Arrays de handles por mecanismo, todos de la longitud de `mixed`. Los
+    // arrays de un solo destino repiten un handle, así que indexarlos con `seq`
+    // elige un destino predecible pagando aun así la misma lectura de array que
+    // el caso heterogéneo.
+    let inners: [&InnerObject; 4] = [&inner; 4];
+    let fns: [fn(u64) -> u64; 4] = [work_function; 4];
+    let enums: [&WorkerEnum; 2] = [&enum_a, &enum_b];
+    let borrows: [&dyn DoWork; 4] = [&worker; 4];
+    let boxes: [&dyn DoWork; 4] = [&*boxed; 4];
+    let arcs: [&dyn DoWork; 4] = [&*arc; 4];
+
+    // La suite de benchmarks: cada entrada empareja un nombre con un cierre de
+    // unidad de trabajo registrado contra el harness `measure`. Ordenada de
+    // más barato a más caro, para que la salida lado a lado se lea
+    // monomorfizado → predecible → impredecible.
+    let mut suite: Vec<Benchmark> = Vec::new();
+
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    suite.push((
+        "Despacho Estático (Genéricos)",
+        Box::new(move || {
+            let k = seq[i & (SEQ_LEN - 1)];
+            i = i.wrapping_add(1);
+            acc = inners[k].action(black_box(acc));
+        }),
+    ));
+
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    suite.push((
+        "Puntero a Función",
+        Box::new(move || {
+            let k = seq[i & (SEQ_LEN - 1)];
+            i = i.wrapping_add(1);
+            // `black_box` sobre el puntero para que el optimizador no pueda
+            // plegar la constante `work_function` de vuelta a una llamada directa.
+            acc = black_box(fns[k])(black_box(acc));
+        }),
+    ));
+
+    // El despacho por enum es el camino barato y predecible del match concreto.
+    // Recorre las variantes en un orden fijo de periodo 2 en lugar de usar la
+    // secuencia aleatoria compartida: el match dentro de `WorkerEnum::do_work`
+    // se predice a la perfección, que es el sentido de comparar enum contra
+    // `dyn`. Solo el caso heterogéneo de abajo pretende ser impredecible.
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    suite.push((
+        "Despacho por Enum (match)",
+        Box::new(move || {
+            let w = enums[i & 1];
+            i = i.wrapping_add(1);
+            acc = w.do_work(black_box(acc));
+        }),
+    ));
+
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    suite.push((
+        "&dyn DoWork prestado",
+        Box::new(move || {
+            let k = seq[i & (SEQ_LEN - 1)];
+            i = i.wrapping_add(1);
+            acc = borrows[k].do_work(black_box(acc));
+        }),
+    ));
+
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    suite.push((
+        "Box<dyn DoWork>",
+        Box::new(move || {
+            let k = seq[i & (SEQ_LEN - 1)];
+            i = i.wrapping_add(1);
+            acc = boxes[k].do_work(black_box(acc));
+        }),
+    ));
+
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    suite.push((
+        "Arc<dyn DoWork>",
+        Box::new(move || {
+            let k = seq[i & (SEQ_LEN - 1)];
+            i = i.wrapping_add(1);
+            acc = arcs[k].do_work(black_box(acc));
+        }),
+    ));
+
+    // El único caso genuinamente impredecible: `mixed` contiene tipos concretos
+    // distintos, así que indexarlo con la secuencia pseudoaleatoria compartida
+    // mantiene el destino de la vtable cambiando y derrota al predictor de
+    // saltos indirectos (ITTAGE), que de otro modo aprendería un ciclo fijo a la
+    // perfección.
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    suite.push((
+        "Heterogéneo &[&dyn DoWork]",
+        Box::new(move || {
+            let k = seq[i & (SEQ_LEN - 1)];
+            i = i.wrapping_add(1);
+            acc = mixed[k].do_work(black_box(acc));
+        }),
+    ));
+
+    let mut results: Vec<(String, BenchResult)> = Vec::new();
+    for (name, f) in suite {
+        let result = measure(&clock, overhead, name, f);
+        if let Some(prev) = baseline.as_ref().and_then(|b| b.get(name)) {
+            let delta = if prev.point != 0.0 {
+                (result.point - prev.point) / prev.point * 100.0
+            } else {
+                0.0
+            };
+            println!("   vs baseline: {delta:+.1}% — {}", verdict(&result, prev));
+        }
+        results.push((name.to_string(), result));
+    }
+
+    if let Some(name) = save_name {
+        match save_baseline(&name, &results) {
+            Ok(()) => println!("\nBaseline '{name}' guardado."),
+            Err(e) => eprintln!("\nFallo al guardar el baseline '{name}': {e}"),
+        }
+    }
+}
@@ -1,39 +1,42 @@
-use std::time::{Instant, Duration};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
-const ITERATIONS: u64 = 1_000_000_000;
+// Number of work units executed inside a single timed sample. Large enough to
+// dwarf the cost of reading the clock, small enough that a sample is quick.
+const INNER_ITERS: u64 = 1_000_000;
+// How many samples `measure` collects per benchmark.
+const SAMPLES: usize = 100;
+// Bootstrap resamples used to derive the 95% confidence interval.
+const RESAMPLES: usize = 100_000;
+// Warm-up runs the closure back-to-back until this much wall time elapses so
+// the CPU reaches a steady turbo clock before we start sampling.
+const WARMUP: Duration = Duration::from_secs(1);
 
 // --- 1. Dynamic Dispatch (like Virtual Functions) ---
 
+// Each work unit takes a value, adds a constant and hands it back through
+// `black_box`, so the optimizer can neither see the result as a constant nor
+// elide the dispatched call.
 trait DoWork {
-    fn do_work(&self);
+    fn do_work(&self, x: u64) -> u64;
 }
 
 struct ConcreteWorker;
 
 impl DoWork for ConcreteWorker {
     #[inline(never)] // Prevent the compiler from being too clever
-    fn do_work(&self) {
-        unsafe { std::arch::asm!(""); }
-    }
-}
-
-fn run_dynamic_dispatch(worker: &dyn DoWork) {
-    for _ in 0..ITERATIONS {
-        worker.do_work();
+    fn do_work(&self, x: u64) -> u64 {
+        black_box(x.wrapping_add(1))
     }
 }
 
 // --- 2. Function Pointer ---
 
 #[inline(never)]
-fn work_function() {
-    unsafe { std::arch::asm!(""); }
-}
-
-fn run_function_pointer(func: fn()) {
-    for _ in 0..ITERATIONS {
-        func();
-    }
+fn work_function(x: u64) -> u64 {
+    black_box(x.wrapping_add(1))
 }
 
 // --- 3. Static Dispatch (Generic Wrapper) ---
@@ -42,41 +45,589 @@ struct InnerObject;
 
 impl InnerObject {
     #[inline(never)]
-    fn action(&self) {
-        unsafe { std::arch::asm!(""); }
+    fn action(&self, x: u64) -> u64 {
+        black_box(x.wrapping_add(1))
+    }
+}
+
+// --- 4. A second concrete worker, so the heterogeneous and enum cases cycle
+// through more than one vtable / variant ---
+
+struct OtherWorker;
+
+impl DoWork for OtherWorker {
+    #[inline(never)]
+    fn do_work(&self, x: u64) -> u64 {
+        black_box(x.wrapping_add(3))
+    }
+}
+
+// Enum dispatch: a concrete match over worker variants, no vtable involved.
+enum WorkerEnum {
+    Concrete(ConcreteWorker),
+    Other(OtherWorker),
+}
+
+impl WorkerEnum {
+    #[inline(never)]
+    fn do_work(&self, x: u64) -> u64 {
+        match self {
+            WorkerEnum::Concrete(w) => w.do_work(x),
+            WorkerEnum::Other(w) => w.do_work(x),
+        }
+    }
+}
+
+// --- Clock sources ---
+
+// Borrowing dipstick's fast-vs-accurate distinction: the harness is generic
+// over a clock so the caller can trade resolution for read overhead. `now`
+// returns an opaque handle and `elapsed_ns` turns two handles' gap into
+// nanoseconds.
+trait Clock {
+    type Handle;
+    fn now(&self) -> Self::Handle;
+    fn elapsed_ns(&self, since: &Self::Handle) -> f64;
+}
+
+// High-accuracy, monotonic clock backed by `Instant`.
+struct InstantClock;
+
+impl Clock for InstantClock {
+    type Handle = Instant;
+
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn elapsed_ns(&self, since: &Instant) -> f64 {
+        since.elapsed().as_nanos() as f64
+    }
+}
+
+// A second clock source backed by the wall clock rather than the monotonic
+// timer, trading resolution and monotonicity for a different read path. Its read
+// cost is not assumed to be lower — on Linux both `Instant` and `SystemTime` go
+// through the vDSO — so calibration times it alongside `InstantClock` and prints
+// both overheads, letting the user see how much the clock source itself costs
+// before trusting a short sample.
+struct CoarseClock;
+
+impl Clock for CoarseClock {
+    type Handle = SystemTime;
+
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn elapsed_ns(&self, since: &SystemTime) -> f64 {
+        since.elapsed().unwrap_or_default().as_nanos() as f64
     }
 }
 
-// A generic function is the most idiomatic way to do this in Rust
-fn run_static_dispatch<F: Fn()>(f: F) {
-    for _ in 0..ITERATIONS {
-        f();
+// --- Sampling harness ---
+
+// A tiny deterministic LCG so the bootstrap is reproducible without pulling in
+// the `rand` crate. The constants are the ones from Knuth's MMIX.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0
+    }
+
+    // Uniform index in `0..n`. `n` is always a small sample count here, so the
+    // modulo bias is negligible.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() >> 33) as usize % n
     }
 }
 
+// Linear-interpolated percentile over an already-sorted slice.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+    if lo == hi {
+        sorted[lo]
+    } else {
+        let frac = rank - lo as f64;
+        sorted[lo] * (1.0 - frac) + sorted[hi] * frac
+    }
+}
+
+// Tukey-fence outlier classification. Returns (mild, severe) counts: samples
+// outside the 1.5·IQR fences are mild, those outside the 3·IQR fences severe.
+fn classify_outliers(sorted: &[f64]) -> (usize, usize) {
+    let q1 = percentile(sorted, 25.0);
+    let q3 = percentile(sorted, 75.0);
+    let iqr = q3 - q1;
+    let mild_lo = q1 - 1.5 * iqr;
+    let mild_hi = q3 + 1.5 * iqr;
+    let severe_lo = q1 - 3.0 * iqr;
+    let severe_hi = q3 + 3.0 * iqr;
+    let mut mild = 0;
+    let mut severe = 0;
+    for &v in sorted {
+        if v < severe_lo || v > severe_hi {
+            severe += 1;
+        } else if v < mild_lo || v > mild_hi {
+            mild += 1;
+        }
+    }
+    (mild, severe)
+}
+
+// 95% confidence interval of the mean by bootstrap resampling: draw RESAMPLES
+// resamples with replacement, take the mean of each, then read the 2.5th and
+// 97.5th percentiles of those means.
+fn bootstrap_ci(samples: &[f64], rng: &mut Lcg) -> (f64, f64) {
+    let n = samples.len();
+    let mut means = Vec::with_capacity(RESAMPLES);
+    for _ in 0..RESAMPLES {
+        let mut sum = 0.0;
+        for _ in 0..n {
+            sum += samples[rng.below(n)];
+        }
+        means.push(sum / n as f64);
+    }
+    means.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    (percentile(&means, 2.5), percentile(&means, 97.5))
+}
+
+// Collect SAMPLES per-iteration timings of `f` through `clock`.
+fn collect_samples<C: Clock>(clock: &C, f: &mut impl FnMut()) -> Vec<f64> {
+    let mut per_iter = Vec::with_capacity(SAMPLES);
+    for _ in 0..SAMPLES {
+        let start = clock.now();
+        for _ in 0..INNER_ITERS {
+            f();
+        }
+        per_iter.push(clock.elapsed_ns(&start) / INNER_ITERS as f64);
+    }
+    per_iter
+}
+
+// Time an empty loop body to estimate the per-iteration overhead of the loop
+// and `clock` itself, so it can be subtracted from real measurements. Uses the
+// median of the samples for robustness.
+fn calibrate<C: Clock>(clock: &C) -> f64 {
+    let mut empty = || {
+        black_box(0u64);
+    };
+    let mut per_iter = collect_samples(clock, &mut empty);
+    per_iter.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    percentile(&per_iter, 50.0)
+}
+
+// A benchmark's point estimate and 95% confidence interval, in ns/iter net of
+// clock overhead. This is what gets persisted as a baseline.
+struct BenchResult {
+    point: f64,
+    ci_lo: f64,
+    ci_hi: f64,
+}
+
+// Measure the per-iteration cost of `f` through `clock`, print a point
+// estimate net of `overhead` with its 95% confidence interval and outlier
+// counts, and return the net result for baseline comparison.
+fn measure<C: Clock>(clock: &C, overhead: f64, name: &str, mut f: impl FnMut()) -> BenchResult {
+    // Warm-up: hammer the closure until the CPU settles.
+    let warm_start = Instant::now();
+    while warm_start.elapsed() < WARMUP {
+        for _ in 0..INNER_ITERS {
+            f();
+        }
+    }
+
+    let per_iter = collect_samples(clock, &mut f);
+
+    let gross = per_iter.iter().sum::<f64>() / per_iter.len() as f64;
+    let net = (gross - overhead).max(0.0);
+
+    let mut sorted = per_iter.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let (mild, severe) = classify_outliers(&sorted);
+
+    let mut rng = Lcg(0x2545F4914F6CDD1D);
+    let (lo, hi) = bootstrap_ci(&per_iter, &mut rng);
+    let ci_lo = (lo - overhead).max(0.0);
+    let ci_hi = (hi - overhead).max(0.0);
+
+    println!("{name}:");
+    println!(
+        "   {net:.3} ns/iter net  [95% CI {ci_lo:.3} .. {ci_hi:.3}]  (gross {gross:.3}, clock {overhead:.3})"
+    );
+    println!("   outliers: {mild} mild, {severe} severe (of {SAMPLES} samples)");
+
+    BenchResult {
+        point: net,
+        ci_lo,
+        ci_hi,
+    }
+}
+
+// --- Baseline persistence ---
+
+// The on-disk file for a named baseline.
+fn baseline_path(name: &str) -> String {
+    format!("{name}.baseline.json")
+}
+
+// Serialize the run's results to a JSON object keyed by benchmark name. The
+// format is hand-rolled to keep the crate dependency-free.
+fn save_baseline(name: &str, results: &[(String, BenchResult)]) -> std::io::Result<()> {
+    let mut out = String::from("{\n");
+    for (i, (bench, r)) in results.iter().enumerate() {
+        out.push_str(&format!(
+            "  {}: {{\"point\": {}, \"ci_lo\": {}, \"ci_hi\": {}}}",
+            json_string(bench),
+            r.point,
+            r.ci_lo,
+            r.ci_hi
+        ));
+        if i + 1 < results.len() {
+            out.push(',');
+        }
+        out.push('\n');
+    }
+    out.push_str("}\n");
+    std::fs::write(baseline_path(name), out)
+}
+
+// Load a previously saved baseline, or `None` if it does not exist yet.
+fn load_baseline(name: &str) -> Option<HashMap<String, BenchResult>> {
+    let text = std::fs::read_to_string(baseline_path(name)).ok()?;
+    Some(parse_baseline(&text))
+}
+
+// Escape a string as a JSON literal. Benchmark names only ever contain quotes
+// or backslashes in pathological cases, but handle them anyway.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// A minimal parser for the subset of JSON `save_baseline` emits: an object of
+// benchmark names to `{point, ci_lo, ci_hi}` objects. Operates on bytes and
+// reassembles string contents through `String::from_utf8_lossy` so non-ASCII
+// benchmark names survive the round-trip.
+struct BaselineParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BaselineParser<'a> {
+    fn skip_ws(&mut self) {
+        while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    // Consume `c` if it is the next non-whitespace byte.
+    fn eat(&mut self, c: u8) -> bool {
+        self.skip_ws();
+        if self.pos < self.bytes.len() && self.bytes[self.pos] == c {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.skip_ws();
+        if self.pos >= self.bytes.len() || self.bytes[self.pos] != b'"' {
+            return None;
+        }
+        self.pos += 1;
+        let mut buf = Vec::new();
+        while self.pos < self.bytes.len() && self.bytes[self.pos] != b'"' {
+            if self.bytes[self.pos] == b'\\' {
+                self.pos += 1;
+                if self.pos >= self.bytes.len() {
+                    break;
+                }
+            }
+            buf.push(self.bytes[self.pos]);
+            self.pos += 1;
+        }
+        self.pos += 1; // closing quote
+        Some(String::from_utf8_lossy(&buf).into_owned())
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        self.skip_ws();
+        let start = self.pos;
+        while self.pos < self.bytes.len()
+            && matches!(
+                self.bytes[self.pos],
+                b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E'
+            )
+        {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.pos])
+            .ok()?
+            .parse()
+            .ok()
+    }
+}
+
+fn parse_baseline(text: &str) -> HashMap<String, BenchResult> {
+    let mut map = HashMap::new();
+    let mut p = BaselineParser {
+        bytes: text.as_bytes(),
+        pos: 0,
+    };
+    if !p.eat(b'{') {
+        return map;
+    }
+    loop {
+        if p.eat(b'}') {
+            break;
+        }
+        let name = match p.string() {
+            Some(n) => n,
+            None => break,
+        };
+        p.eat(b':');
+        p.eat(b'{');
+        let (mut point, mut ci_lo, mut ci_hi) = (0.0, 0.0, 0.0);
+        loop {
+            if p.eat(b'}') {
+                break;
+            }
+            let field = match p.string() {
+                Some(f) => f,
+                None => break,
+            };
+            p.eat(b':');
+            let value = p.number().unwrap_or(0.0);
+            match field.as_str() {
+                "point" => point = value,
+                "ci_lo" => ci_lo = value,
+                "ci_hi" => ci_hi = value,
+                _ => {}
+            }
+            p.eat(b',');
+        }
+        map.insert(name, BenchResult { point, ci_lo, ci_hi });
+        p.eat(b',');
+    }
+    map
+}
+
+// Compare a fresh result against a saved one. Verdict is decided by whether the
+// confidence intervals overlap; lower ns/iter is better.
+fn verdict(new: &BenchResult, old: &BenchResult) -> &'static str {
+    if new.ci_hi < old.ci_lo {
+        "improved"
+    } else if new.ci_lo > old.ci_hi {
+        "regressed"
+    } else {
+        "within noise"
+    }
+}
+
+// A named work unit registered against the `measure` harness.
+type Benchmark<'a> = (&'a str, Box<dyn FnMut() + 'a>);
 
 fn main() {
-    // --- Test 1: Dynamic Dispatch ---
-    println!("1. Dynamic Dispatch (dyn Trait) Benchmark...");
-    let worker = ConcreteWorker;
-    let start = Instant::now();
-    run_dynamic_dispatch(&worker);
-    let duration = start.elapsed();
-    println!("   Total time: {:.6?} seconds", duration.as_secs_f64());
-
-    // --- Test 2: Function Pointer ---
-    println!("\n2. Function Pointer Benchmark...");
-    let start = Instant::now();
-    run_function_pointer(work_function);
-    let duration = start.elapsed();
-    println!("   Total time: {:.6?} seconds", duration.as_secs_f64());
-
-    // --- Test 3: Static Dispatch (Generics) ---
-    println!("\n3. Static Dispatch (Generics) Benchmark...");
+    // CLI: `--baseline <name>` loads a saved baseline to compare against,
+    // `--save-baseline <name>` writes this run's results as a baseline.
+    let args: Vec<String> = std::env::args().collect();
+    let mut load_name = None;
+    let mut save_name = None;
+    let mut it = args.iter().skip(1);
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--baseline" => load_name = it.next().cloned(),
+            "--save-baseline" => save_name = it.next().cloned(),
+            _ => {}
+        }
+    }
+
+    let baseline = match load_name.as_deref() {
+        Some(name) => match load_baseline(name) {
+            Some(b) => Some(b),
+            None => {
+                println!("No baseline '{name}' found; skipping comparison.\n");
+                None
+            }
+        },
+        None => None,
+    };
+
+    // Reported numbers use the accurate clock; calibration subtracts its read
+    // overhead. The coarse clock's overhead is printed for comparison.
+    let clock = InstantClock;
+    let overhead = calibrate(&clock);
+    let coarse_overhead = calibrate(&CoarseClock);
+    println!(
+        "Clock overhead: accurate {overhead:.3} ns/iter, coarse {coarse_overhead:.3} ns/iter\n"
+    );
+
+    // Owners of the dispatched objects. They must outlive the registry, so
+    // they are declared up front.
     let inner = InnerObject;
-    let wrapper = || inner.action(); // Using a closure is the most idiomatic approach
-    let start = Instant::now();
-    run_static_dispatch(wrapper);
-    let duration = start.elapsed();
-    println!("   Total time: {:.6?} seconds", duration.as_secs_f64());
+    let worker = ConcreteWorker;
+    let enum_a = WorkerEnum::Concrete(ConcreteWorker);
+    let enum_b = WorkerEnum::Other(OtherWorker);
+    let boxed: Box<dyn DoWork> = Box::new(ConcreteWorker);
+    let arc: Arc<dyn DoWork> = Arc::new(ConcreteWorker);
+    let a = ConcreteWorker;
+    let b = OtherWorker;
+    let mixed: [&dyn DoWork; 4] = [&a, &b, &b, &a];
+
+    // A pseudo-random selector sequence, precomputed once and shared by every
+    // benchmark so the per-iteration non-dispatch cost (one masked array read)
+    // is identical across the suite and only the dispatch mechanism varies.
+    // Every closure indexes a length-`mixed.len()` handle array the same way;
+    // the single-target cases fill that array with identical handles so the
+    // target stays predictable, while the heterogeneous case fills it with
+    // distinct concrete types so the vtable target is genuinely unpredictable.
+    const SEQ_LEN: usize = 256;
+    let mut seq_rng = Lcg(0x9E3779B97F4A7C15);
+    let seq: [usize; SEQ_LEN] = std::array::from_fn(|_| seq_rng.below(mixed.len()));
+
+    // Per-mechanism handle arrays, all the length of `mixed`. The single-target
+    // arrays repeat one handle, so indexing them with `seq` picks a predictable
+    // target while still paying the same array read as the heterogeneous case.
+    let inners: [&InnerObject; 4] = [&inner; 4];
+    let fns: [fn(u64) -> u64; 4] = [work_function; 4];
+    let enums: [&WorkerEnum; 2] = [&enum_a, &enum_b];
+    let borrows: [&dyn DoWork; 4] = [&worker; 4];
+    let boxes: [&dyn DoWork; 4] = [&*boxed; 4];
+    let arcs: [&dyn DoWork; 4] = [&*arc; 4];
+
+    // The benchmark suite: each entry pairs a name with a work unit closure
+    // registered against the `measure` harness. Ordered cheapest-first so the
+    // side-by-side output reads monomorphized → predictable → unpredictable.
+    let mut suite: Vec<Benchmark> = Vec::new();
+
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    suite.push((
+        "Static Dispatch (Generics)",
+        Box::new(move || {
+            let k = seq[i & (SEQ_LEN - 1)];
+            i = i.wrapping_add(1);
+            acc = inners[k].action(black_box(acc));
+        }),
+    ));
+
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    suite.push((
+        "Function Pointer",
+        Box::new(move || {
+            let k = seq[i & (SEQ_LEN - 1)];
+            i = i.wrapping_add(1);
+            // `black_box` the pointer so the optimizer cannot fold the constant
+            // `work_function` back into a direct call.
+            acc = black_box(fns[k])(black_box(acc));
+        }),
+    ));
+
+    // Enum dispatch is the cheap, predictable concrete-match path. It walks the
+    // variants in a fixed period-2 order rather than through the shared random
+    // sequence: the match inside `WorkerEnum::do_work` is branch-predicted
+    // perfectly, which is the whole point of the enum-vs-`dyn` comparison. Only
+    // the heterogeneous case below is meant to be unpredictable.
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    suite.push((
+        "Enum Dispatch (match)",
+        Box::new(move || {
+            let w = enums[i & 1];
+            i = i.wrapping_add(1);
+            acc = w.do_work(black_box(acc));
+        }),
+    ));
+
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    suite.push((
+        "Borrowed &dyn DoWork",
+        Box::new(move || {
+            let k = seq[i & (SEQ_LEN - 1)];
+            i = i.wrapping_add(1);
+            acc = borrows[k].do_work(black_box(acc));
+        }),
+    ));
+
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    suite.push((
+        "Box<dyn DoWork>",
+        Box::new(move || {
+            let k = seq[i & (SEQ_LEN - 1)];
+            i = i.wrapping_add(1);
+            acc = boxes[k].do_work(black_box(acc));
+        }),
+    ));
+
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    suite.push((
+        "Arc<dyn DoWork>",
+        Box::new(move || {
+            let k = seq[i & (SEQ_LEN - 1)];
+            i = i.wrapping_add(1);
+            acc = arcs[k].do_work(black_box(acc));
+        }),
+    ));
+
+    // The one genuinely unpredictable case: `mixed` holds distinct concrete
+    // types, so indexing it with the shared pseudo-random sequence keeps the
+    // vtable target changing and defeats the indirect-branch predictor (ITTAGE),
+    // which would otherwise learn a fixed cycle perfectly.
+    let mut acc = 0u64;
+    let mut i = 0usize;
+    suite.push((
+        "Heterogeneous &[&dyn DoWork]",
+        Box::new(move || {
+            let k = seq[i & (SEQ_LEN - 1)];
+            i = i.wrapping_add(1);
+            acc = mixed[k].do_work(black_box(acc));
+        }),
+    ));
+
+    let mut results: Vec<(String, BenchResult)> = Vec::new();
+    for (name, f) in suite {
+        let result = measure(&clock, overhead, name, f);
+        if let Some(prev) = baseline.as_ref().and_then(|b| b.get(name)) {
+            let delta = if prev.point != 0.0 {
+                (result.point - prev.point) / prev.point * 100.0
+            } else {
+                0.0
+            };
+            println!("   vs baseline: {delta:+.1}% — {}", verdict(&result, prev));
+        }
+        results.push((name.to_string(), result));
+    }
+
+    if let Some(name) = save_name {
+        match save_baseline(&name, &results) {
+            Ok(()) => println!("\nSaved baseline '{name}'."),
+            Err(e) => eprintln!("\nFailed to save baseline '{name}': {e}"),
+        }
+    }
 }